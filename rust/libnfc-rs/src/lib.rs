@@ -7,8 +7,49 @@ use std::ptr;
 #[cfg(feature = "nfc_secure")]
 mod nfc_secure;
 
+/// Overwrite `buf` with zeros before it is dropped. Connstrings often
+/// carry secrets (device keys, passwords passed as parameters), so the
+/// intermediate `Vec<u8>` buffers built while parsing/building them
+/// should not linger in freed heap memory. Delegates to the
+/// `nfc_secure` module's erase routine when the feature is enabled; a
+/// no-op build without that feature leaves the previous behavior
+/// unchanged.
+#[cfg(feature = "nfc_secure")]
+fn secure_wipe(buf: &mut [u8]) {
+    nfc_secure::secure_zero_bytes(buf);
+    #[cfg(test)]
+    TEST_WIPE_COUNT.with(|count| count.set(count.get() + 1));
+}
+
+#[cfg(not(feature = "nfc_secure"))]
+fn secure_wipe(_buf: &mut [u8]) {
+    #[cfg(test)]
+    TEST_WIPE_COUNT.with(|count| count.set(count.get() + 1));
+}
+
+// Test-only instrumentation so connstring tests can confirm `secure_wipe`
+// actually ran on both the success and error return paths, without
+// reaching into the private `Vec<u8>` buffers those functions own.
+#[cfg(test)]
 thread_local! {
-    static LAST_ERROR: RefCell<Option<CString>> = RefCell::new(None);
+    static TEST_WIPE_COUNT: std::cell::Cell<usize> = std::cell::Cell::new(0);
+}
+
+#[cfg(test)]
+pub fn test_wipe_count() -> usize {
+    TEST_WIPE_COUNT.with(|count| count.get())
+}
+
+#[cfg(test)]
+pub fn test_reset_wipe_count() {
+    TEST_WIPE_COUNT.with(|count| count.set(0));
+}
+
+thread_local! {
+    // Keeps the error code alongside the free-form message so
+    // `nfc_get_last_errno` can report what `nfc_strerror` should be
+    // called with, without the caller having to parse the message text.
+    static LAST_ERROR: RefCell<Option<(c_int, CString)>> = RefCell::new(None);
 }
 
 const NFC_COMMON_SUCCESS: c_int = 0;
@@ -91,12 +132,12 @@ fn log_debug(message: &str) {
     log_message(LOG_PRIORITY_DEBUG, message);
 }
 
-fn set_last_error_message<S: Into<String>>(message: S) {
+fn set_last_error<S: Into<String>>(code: c_int, message: S) {
     let message = message.into();
     LAST_ERROR.with(|cell| {
         let cstr = CString::new(message)
             .unwrap_or_else(|_| CString::new("error message contained interior NUL").unwrap());
-        *cell.borrow_mut() = Some(cstr);
+        *cell.borrow_mut() = Some((code, cstr));
     });
 }
 
@@ -110,7 +151,7 @@ fn ensure_utf8(cstr: &CStr, context: &str) -> Result<(), c_int> {
     if cstr.to_str().is_err() {
         let message = format!("{} contains non UTF-8 data", context);
         log_error(&message);
-        set_last_error_message(message);
+        set_last_error(NFC_COMMON_INVALID, message);
         return Err(NFC_COMMON_INVALID);
     }
     Ok(())
@@ -119,7 +160,7 @@ fn ensure_utf8(cstr: &CStr, context: &str) -> Result<(), c_int> {
 fn validate_non_null(ptr: *const c_char, message: &str) -> Result<&CStr, c_int> {
     if ptr.is_null() {
         log_error(message);
-        set_last_error_message(message);
+        set_last_error(NFC_COMMON_INVALID, message);
         return Err(NFC_COMMON_INVALID);
     }
 
@@ -129,7 +170,7 @@ fn validate_non_null(ptr: *const c_char, message: &str) -> Result<&CStr, c_int>
 fn validate_mut_ptr(ptr: *mut c_char, message: &str) -> Result<*mut c_char, c_int> {
     if ptr.is_null() {
         log_error(message);
-        set_last_error_message(message);
+        set_last_error(NFC_COMMON_INVALID, message);
         return Err(NFC_COMMON_INVALID);
     }
     Ok(ptr)
@@ -137,7 +178,7 @@ fn validate_mut_ptr(ptr: *mut c_char, message: &str) -> Result<*mut c_char, c_in
 
 fn set_error_and_return(code: c_int, message: String) -> c_int {
     log_error(&message);
-    set_last_error_message(message);
+    set_last_error(code, message);
     code
 }
 
@@ -166,6 +207,79 @@ fn split_at_first<'a>(data: &'a [u8], delimiter: u8) -> (&'a [u8], Option<&'a [u
     }
 }
 
+const HEX_DIGITS_UPPER: &[u8; 16] = b"0123456789ABCDEF";
+
+/// Whether `b` must be `%XX`-escaped in a connstring parameter value:
+/// the `:`/`=` segment delimiters, the `%` escape character itself, any
+/// control byte, or anything outside printable ASCII.
+fn connstring_value_byte_needs_escape(b: u8) -> bool {
+    b == b':' || b == b'=' || b == b'%' || b.is_ascii_control() || b > 0x7E
+}
+
+/// Percent-encode `value` for embedding as a connstring parameter
+/// value: bytes that would otherwise be mistaken for a delimiter (or
+/// are not printable ASCII) are emitted as `%XX` with uppercase hex
+/// digits, so a binary key or a `C:\...` path round-trips intact.
+fn percent_encode_connstring_value(value: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(value.len());
+    for &b in value {
+        if connstring_value_byte_needs_escape(b) {
+            out.push(b'%');
+            out.push(HEX_DIGITS_UPPER[(b >> 4) as usize]);
+            out.push(HEX_DIGITS_UPPER[(b & 0x0F) as usize]);
+        } else {
+            out.push(b);
+        }
+    }
+    out
+}
+
+fn hex_digit_value(b: u8) -> Option<u8> {
+    match b {
+        b'0'..=b'9' => Some(b - b'0'),
+        b'a'..=b'f' => Some(b - b'a' + 10),
+        b'A'..=b'F' => Some(b - b'A' + 10),
+        _ => None,
+    }
+}
+
+/// Reverse of [`percent_encode_connstring_value`]. Returns an error
+/// message describing the offending byte offset when a `%` is not
+/// followed by exactly two valid hex digits.
+fn percent_decode_connstring_value(value: &[u8]) -> Result<Vec<u8>, String> {
+    let mut out = Vec::with_capacity(value.len());
+    let mut i = 0usize;
+    while i < value.len() {
+        let b = value[i];
+        if b == b'%' {
+            if i + 3 > value.len() {
+                return Err(format!(
+                    "Truncated %-escape at offset {} in connstring parameter value",
+                    i
+                ));
+            }
+            match (hex_digit_value(value[i + 1]), hex_digit_value(value[i + 2])) {
+                (Some(hi), Some(lo)) => {
+                    out.push((hi << 4) | lo);
+                    i += 3;
+                }
+                _ => {
+                    return Err(format!(
+                        "Invalid %-escape '%{}{}' at offset {} in connstring parameter value",
+                        value[i + 1] as char,
+                        value[i + 2] as char,
+                        i
+                    ));
+                }
+            }
+        } else {
+            out.push(b);
+            i += 1;
+        }
+    }
+    Ok(out)
+}
+
 unsafe fn alloc_and_copy(segment: &[u8]) -> Result<*mut c_char, ()> {
     let length = segment.len().min(NFC_BUFSIZE_CONNSTRING);
     let size = length + 1;
@@ -193,12 +307,16 @@ where
         Err(_) => {
             let message = "panic in connstring_decode";
             log_error(message);
-            set_last_error_message(message);
+            set_last_error(NFC_COMMON_ERROR, message);
             0
         }
     }
 }
 
+/// Parse `param_name`'s value out of `connstring`, percent-decoding
+/// `%XX` escapes back to raw bytes so a value that legitimately
+/// contains `:`, `=`, or non-printable bytes round-trips intact. See
+/// [`nfc_parse_connstring_raw`] for the unescaped, legacy behavior.
 #[no_mangle]
 pub unsafe extern "C" fn nfc_parse_connstring(
     connstring: *const c_char,
@@ -206,6 +324,46 @@ pub unsafe extern "C" fn nfc_parse_connstring(
     param_name: *const c_char,
     param_value: *mut c_char,
     param_value_size: size_t,
+) -> c_int {
+    nfc_parse_connstring_impl(
+        connstring,
+        prefix,
+        param_name,
+        param_value,
+        param_value_size,
+        true,
+    )
+}
+
+/// Identical to [`nfc_parse_connstring`] but does not percent-decode
+/// the extracted value, matching this crate's original behavior for
+/// callers that already handle `:`/`=`-delimited values themselves and
+/// should not have `%XX` sequences in their data reinterpreted.
+#[no_mangle]
+pub unsafe extern "C" fn nfc_parse_connstring_raw(
+    connstring: *const c_char,
+    prefix: *const c_char,
+    param_name: *const c_char,
+    param_value: *mut c_char,
+    param_value_size: size_t,
+) -> c_int {
+    nfc_parse_connstring_impl(
+        connstring,
+        prefix,
+        param_name,
+        param_value,
+        param_value_size,
+        false,
+    )
+}
+
+unsafe fn nfc_parse_connstring_impl(
+    connstring: *const c_char,
+    prefix: *const c_char,
+    param_name: *const c_char,
+    param_value: *mut c_char,
+    param_value_size: size_t,
+    escape: bool,
 ) -> c_int {
     if param_value_size == 0 {
         return set_error_and_return(
@@ -253,7 +411,7 @@ pub unsafe extern "C" fn nfc_parse_connstring(
             conn_display, prefix_display
         );
         log_debug(&message);
-        set_last_error_message(message);
+        set_last_error(NFC_COMMON_ERROR, message);
         return NFC_COMMON_ERROR;
     }
 
@@ -277,13 +435,18 @@ pub unsafe extern "C" fn nfc_parse_connstring(
         i += 1;
     }
 
+    // `pattern` held the (non-secret) parameter name, but it is scanned
+    // over the same connstring that may carry a secret value; wipe it
+    // now that the search is done, whether or not it matched.
+    secure_wipe(&mut pattern);
+
     let value_start_idx = match value_start_idx {
         Some(idx) => idx,
         None => {
             let param_display = String::from_utf8_lossy(param_name_bytes);
             let message = format!("Parameter '{}' not found in connstring", param_display);
             log_debug(&message);
-            set_last_error_message(message);
+            set_last_error(NFC_COMMON_ERROR, message);
             return NFC_COMMON_ERROR;
         }
     };
@@ -293,7 +456,26 @@ pub unsafe extern "C" fn nfc_parse_connstring(
         .iter()
         .position(|&b| b == b':')
         .unwrap_or(value_slice.len());
-    let value_bytes = &value_slice[..value_end];
+    let raw_value_bytes = &value_slice[..value_end];
+
+    // Only populated when `escape` decodes a fresh `Vec<u8>` that this
+    // function owns; the raw (non-escaped) path borrows directly from
+    // the caller's connstring buffer, which is not ours to wipe.
+    let mut decoded_value: Option<Vec<u8>> = None;
+    let value_bytes: &[u8] = if escape {
+        let decoded = match percent_decode_connstring_value(raw_value_bytes) {
+            Ok(bytes) => bytes,
+            Err(message) => {
+                log_error(&message);
+                set_last_error(NFC_COMMON_INVALID, message);
+                return NFC_COMMON_INVALID;
+            }
+        };
+        decoded_value = Some(decoded);
+        decoded_value.as_deref().unwrap()
+    } else {
+        raw_value_bytes
+    };
 
     let dest_capacity = param_value_size as usize;
     if value_bytes.len() >= dest_capacity {
@@ -302,8 +484,11 @@ pub unsafe extern "C" fn nfc_parse_connstring(
             value_bytes.len(),
             dest_capacity
         );
-        set_last_error_message(message.clone());
+        set_last_error(NFC_COMMON_ERROR, message.clone());
         log_error(&message);
+        if let Some(ref mut decoded) = decoded_value {
+            secure_wipe(decoded);
+        }
         return NFC_COMMON_ERROR;
     }
 
@@ -323,11 +508,20 @@ pub unsafe extern "C" fn nfc_parse_connstring(
         param_display, value_display
     ));
 
+    if let Some(ref mut decoded) = decoded_value {
+        secure_wipe(decoded);
+    }
+
     reset_last_error();
 
     NFC_COMMON_SUCCESS
 }
 
+/// Build a connstring from `driver_name`, `param_name`, and
+/// `param_value`, percent-encoding any byte in `param_value` that
+/// would otherwise be mistaken for a `:`/`=` delimiter (or is not
+/// printable ASCII) as `%XX`. See [`nfc_build_connstring_raw`] for the
+/// unescaped, legacy behavior.
 #[no_mangle]
 pub unsafe extern "C" fn nfc_build_connstring(
     dest: *mut c_char,
@@ -335,6 +529,31 @@ pub unsafe extern "C" fn nfc_build_connstring(
     driver_name: *const c_char,
     param_name: *const c_char,
     param_value: *const c_char,
+) -> c_int {
+    nfc_build_connstring_impl(dest, dest_size, driver_name, param_name, param_value, true)
+}
+
+/// Identical to [`nfc_build_connstring`] but does not percent-encode
+/// `param_value`, matching this crate's original behavior for callers
+/// that already guarantee their value contains no `:`/`=` bytes.
+#[no_mangle]
+pub unsafe extern "C" fn nfc_build_connstring_raw(
+    dest: *mut c_char,
+    dest_size: size_t,
+    driver_name: *const c_char,
+    param_name: *const c_char,
+    param_value: *const c_char,
+) -> c_int {
+    nfc_build_connstring_impl(dest, dest_size, driver_name, param_name, param_value, false)
+}
+
+unsafe fn nfc_build_connstring_impl(
+    dest: *mut c_char,
+    dest_size: size_t,
+    driver_name: *const c_char,
+    param_name: *const c_char,
+    param_value: *const c_char,
+    escape: bool,
 ) -> c_int {
     if dest_size == 0 {
         return set_error_and_return(
@@ -375,7 +594,17 @@ pub unsafe extern "C" fn nfc_build_connstring(
 
     let driver_bytes = driver_name_c.to_bytes();
     let param_name_bytes = param_name_c.to_bytes();
-    let param_value_bytes = param_value_c.to_bytes();
+    let raw_param_value_bytes = param_value_c.to_bytes();
+    // Only populated when `escape` produces a fresh `Vec<u8>` that this
+    // function owns; the raw (non-escaped) path borrows directly from the
+    // caller's param_value buffer, which is not ours to wipe.
+    let mut encoded_param_value: Option<Vec<u8>> = None;
+    let param_value_bytes: &[u8] = if escape {
+        encoded_param_value = Some(percent_encode_connstring_value(raw_param_value_bytes));
+        encoded_param_value.as_deref().unwrap()
+    } else {
+        raw_param_value_bytes
+    };
 
     let mut result = Vec::with_capacity(
         driver_bytes.len() + 1 + param_name_bytes.len() + 1 + param_value_bytes.len(),
@@ -386,14 +615,19 @@ pub unsafe extern "C" fn nfc_build_connstring(
     result.push(b'=');
     result.extend_from_slice(param_value_bytes);
 
+    if let Some(ref mut encoded) = encoded_param_value {
+        secure_wipe(encoded);
+    }
+
     let needed = result.len() + 1; // include null terminator
     if needed > dest_size as usize {
         let message = format!(
             "Connection string buffer overflow (need {} bytes, have {})",
             needed, dest_size
         );
-        set_last_error_message(message.clone());
+        set_last_error(NFC_COMMON_ERROR, message.clone());
         log_error(&message);
+        secure_wipe(&mut result);
         return NFC_COMMON_ERROR;
     }
 
@@ -405,19 +639,62 @@ pub unsafe extern "C" fn nfc_build_connstring(
     let display = String::from_utf8_lossy(&result);
     log_debug(&format!("Built connection string: '{}'", display));
 
+    secure_wipe(&mut result);
+
     reset_last_error();
 
     NFC_COMMON_SUCCESS
 }
 
+/// Translate a result code (one of the `NFC_COMMON_*` constants, or a
+/// negative errno such as the `-EINVAL` that `NFC_COMMON_INVALID`
+/// happens to equal) into a static, human-readable description.
+///
+/// Implemented the way nix's `errno` module resolves a raw code: a
+/// single match over the common negative-errno values, falling back to
+/// "Unknown error" for anything else. The returned pointer is a
+/// `'static` string literal baked into the binary, so unlike
+/// `nfc_get_last_error`'s thread-local buffer it stays valid
+/// indefinitely and can be read from any thread.
+#[no_mangle]
+pub extern "C" fn nfc_strerror(code: c_int) -> *const c_char {
+    let bytes: &'static [u8] = match code {
+        NFC_COMMON_SUCCESS => b"Success\0",
+        NFC_COMMON_ERROR => b"Generic libnfc-rs error\0",
+        c if c == -(libc::EINVAL as c_int) => b"Invalid argument\0",
+        c if c == -(libc::ENOMEM as c_int) => b"Cannot allocate memory\0",
+        c if c == -(libc::ENODEV as c_int) => b"No such device\0",
+        c if c == -(libc::EIO as c_int) => b"Input/output error\0",
+        c if c == -(libc::ETIMEDOUT as c_int) => b"Connection timed out\0",
+        c if c == -(libc::EACCES as c_int) => b"Permission denied\0",
+        c if c == -(libc::EBUSY as c_int) => b"Device or resource busy\0",
+        c if c == -(libc::ENOSPC as c_int) => b"No space left on device\0",
+        c if c == -(libc::EAGAIN as c_int) => b"Resource temporarily unavailable\0",
+        _ => b"Unknown error\0",
+    };
+    bytes.as_ptr() as *const c_char
+}
+
 #[no_mangle]
 pub extern "C" fn nfc_get_last_error() -> *const c_char {
     LAST_ERROR.with(|cell| match cell.borrow().as_ref() {
-        Some(message) => message.as_ptr(),
+        Some((_, message)) => message.as_ptr(),
         None => ptr::null(),
     })
 }
 
+/// Return the `c_int` code that was stored alongside the current
+/// thread's last error message (via `nfc_set_last_error` or any FFI
+/// call that failed), or `NFC_COMMON_SUCCESS` when no error is set.
+/// Pass the result to `nfc_strerror` for a human-readable description.
+#[no_mangle]
+pub extern "C" fn nfc_get_last_errno() -> c_int {
+    LAST_ERROR.with(|cell| match cell.borrow().as_ref() {
+        Some((code, _)) => *code,
+        None => NFC_COMMON_SUCCESS,
+    })
+}
+
 #[no_mangle]
 pub extern "C" fn nfc_clear_last_error() {
     reset_last_error();
@@ -432,7 +709,7 @@ pub unsafe extern "C" fn nfc_set_last_error(message: *const c_char) {
 
     let c_message = CStr::from_ptr(message);
     let owned = String::from_utf8_lossy(c_message.to_bytes()).into_owned();
-    set_last_error_message(owned);
+    set_last_error(NFC_COMMON_ERROR, owned);
 }
 
 /// Free memory allocated by Rust FFI helpers
@@ -547,6 +824,162 @@ pub unsafe extern "C" fn connstring_decode(
     })
 }
 
+/// One `key=value` token out of a connstring, as reported by
+/// [`nfc_connstring_parse_all`]. `key` and `value` are each malloc'd,
+/// NUL-terminated, and owned by the caller until freed (individually via
+/// `nfc_rs_free`, or all at once via [`nfc_connstring_free_all`]).
+///
+/// The leading driver/bus segment (the part before the first `:`) has no
+/// `=` of its own, so it is reported under the sentinel empty-string key
+/// (`""`) with `value` set to that segment.
+#[repr(C)]
+pub struct NfcConnParam {
+    pub key: *mut c_char,
+    pub value: *mut c_char,
+}
+
+/// Tokenize `connstring` once into every `:`-separated section, splitting
+/// each section on its first `=` into a key/value pair, and write the
+/// result into the caller-allocated `out` array (capacity `out_cap`).
+///
+/// Unlike [`nfc_parse_connstring`], which re-scans the whole string to
+/// pull out one named parameter per call, this extracts every parameter
+/// in a single pass. The leading driver/bus segment is reported under the
+/// sentinel empty-string key, matching `connstring_decode`'s notion of
+/// the first segment.
+///
+/// Once the arguments pass validation, `out_len` receives the number of
+/// tokens the connstring actually contains, whether or not `out_cap`
+/// was large enough to hold them (it is reset to `0` if an allocation
+/// fails): callers can pass `out_cap == 0` (with `out` null) to size the
+/// array, then call again with a big-enough buffer to fill it. On
+/// success each entry's `key`/`value` must eventually be released, most
+/// conveniently via [`nfc_connstring_free_all`].
+#[no_mangle]
+pub unsafe extern "C" fn nfc_connstring_parse_all(
+    connstring: *const c_char,
+    out: *mut NfcConnParam,
+    out_cap: size_t,
+    out_len: *mut size_t,
+) -> c_int {
+    nfc_connstring_parse_all_impl(connstring, out, out_cap, out_len)
+}
+
+unsafe fn nfc_connstring_parse_all_impl(
+    connstring: *const c_char,
+    out: *mut NfcConnParam,
+    out_cap: size_t,
+    out_len: *mut size_t,
+) -> c_int {
+    let connstring_c = match validate_non_null(connstring, "NULL connstring in parsing") {
+        Ok(value) => value,
+        Err(code) => return code,
+    };
+    if out_len.is_null() {
+        return set_error_and_return(
+            NFC_COMMON_INVALID,
+            "NULL out_len in connstring parsing".to_string(),
+        );
+    }
+    if out_cap > 0 && out.is_null() {
+        return set_error_and_return(
+            NFC_COMMON_INVALID,
+            "NULL out buffer with non-zero out_cap in connstring parsing".to_string(),
+        );
+    }
+
+    if let Err(code) = ensure_utf8(connstring_c, "connstring") {
+        return code;
+    }
+
+    let mut sections: Vec<&[u8]> = Vec::new();
+    let mut remaining = connstring_c.to_bytes();
+    loop {
+        let (section, rest) = split_at_first(remaining, b':');
+        sections.push(section);
+        match rest {
+            Some(rest) => remaining = rest,
+            None => break,
+        }
+    }
+
+    let needed = sections.len();
+    *out_len = needed as size_t;
+    if needed > out_cap as usize {
+        let message = format!(
+            "connstring has {} parameters, output buffer holds only {}",
+            needed, out_cap
+        );
+        set_last_error(NFC_COMMON_ERROR, message.clone());
+        log_error(&message);
+        return NFC_COMMON_ERROR;
+    }
+
+    for (i, section) in sections.iter().enumerate() {
+        let (key, value) = split_at_first(section, b'=');
+        let (key_bytes, value_bytes) = match value {
+            Some(value) => (key, value),
+            // No `=` in this section: treat it as a positional value (the
+            // driver name at index 0, or a bare flag-style parameter).
+            None => (&[][..], *section),
+        };
+
+        let key_ptr = match alloc_and_copy(key_bytes) {
+            Ok(ptr_value) => ptr_value,
+            Err(()) => {
+                free_connstring_params(out, i);
+                *out_len = 0;
+                return NFC_COMMON_ERROR;
+            }
+        };
+        let value_ptr = match alloc_and_copy(value_bytes) {
+            Ok(ptr_value) => ptr_value,
+            Err(()) => {
+                libc::free(key_ptr as *mut c_void);
+                free_connstring_params(out, i);
+                *out_len = 0;
+                return NFC_COMMON_ERROR;
+            }
+        };
+
+        *out.add(i) = NfcConnParam {
+            key: key_ptr,
+            value: value_ptr,
+        };
+    }
+
+    reset_last_error();
+
+    NFC_COMMON_SUCCESS
+}
+
+/// Free the `key`/`value` pointers of the first `len` entries of `out`,
+/// used to unwind a partially-filled array when `nfc_connstring_parse_all`
+/// fails partway through.
+unsafe fn free_connstring_params(out: *mut NfcConnParam, len: usize) {
+    for i in 0..len {
+        let entry = &*out.add(i);
+        if !entry.key.is_null() {
+            libc::free(entry.key as *mut c_void);
+        }
+        if !entry.value.is_null() {
+            libc::free(entry.value as *mut c_void);
+        }
+    }
+}
+
+/// Free every `key`/`value` pointer produced by a successful
+/// [`nfc_connstring_parse_all`] call. Mirrors [`nfc_rs_free`]'s
+/// null-tolerant `libc::free` convention, applied to each of the `len`
+/// entries in `entries`.
+#[no_mangle]
+pub unsafe extern "C" fn nfc_connstring_free_all(entries: *mut NfcConnParam, len: size_t) {
+    if entries.is_null() {
+        return;
+    }
+    free_connstring_params(entries, len as usize);
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -672,4 +1105,326 @@ mod tests {
             assert!(logged.unwrap().contains("does not match prefix"));
         }
     }
+
+    #[test]
+    fn last_errno_tracks_the_stored_code() {
+        unsafe {
+            nfc_clear_last_error();
+            assert_eq!(nfc_get_last_errno(), NFC_COMMON_SUCCESS);
+
+            let msg = CString::new("manual error").unwrap();
+            nfc_set_last_error(msg.as_ptr());
+            assert_eq!(nfc_get_last_errno(), NFC_COMMON_ERROR);
+
+            nfc_clear_last_error();
+            assert_eq!(nfc_get_last_errno(), NFC_COMMON_SUCCESS);
+        }
+    }
+
+    #[test]
+    fn parse_connstring_failure_sets_last_errno() {
+        unsafe {
+            nfc_clear_last_error();
+            let conn = CString::new("pn53x_usb:/dev/usb").unwrap();
+            let prefix = CString::new("pn532").unwrap();
+            let mut buf = [0u8; 64];
+            let rc = nfc_parse_connstring(
+                conn.as_ptr(),
+                prefix.as_ptr(),
+                CString::new("param").unwrap().as_ptr(),
+                buf.as_mut_ptr() as *mut c_char,
+                buf.len(),
+            );
+            assert_eq!(rc, NFC_COMMON_ERROR);
+            assert_eq!(nfc_get_last_errno(), NFC_COMMON_ERROR);
+        }
+    }
+
+    #[test]
+    fn strerror_covers_known_and_unknown_codes() {
+        let invalid = unsafe { CStr::from_ptr(nfc_strerror(NFC_COMMON_INVALID)) };
+        assert_eq!(invalid.to_str().unwrap(), "Invalid argument");
+
+        let success = unsafe { CStr::from_ptr(nfc_strerror(NFC_COMMON_SUCCESS)) };
+        assert_eq!(success.to_str().unwrap(), "Success");
+
+        let unmapped = unsafe { CStr::from_ptr(nfc_strerror(-9999)) };
+        assert_eq!(unmapped.to_str().unwrap(), "Unknown error");
+    }
+
+    #[test]
+    fn percent_encode_decode_round_trips_delimiters_and_binary_bytes() {
+        let value: &[u8] = b"a:b=c%d\x00\x01\xffz";
+        let encoded = percent_encode_connstring_value(value);
+        assert!(!encoded.contains(&b':'));
+        assert!(!encoded.contains(&b'='));
+        assert_eq!(encoded[..3], *b"a%3");
+
+        let decoded = percent_decode_connstring_value(&encoded).unwrap();
+        assert_eq!(decoded, value);
+    }
+
+    #[test]
+    fn percent_decode_rejects_truncated_and_invalid_escapes() {
+        assert!(percent_decode_connstring_value(b"abc%2").is_err());
+        assert!(percent_decode_connstring_value(b"abc%zz").is_err());
+        assert!(percent_decode_connstring_value(b"abc%2g").is_err());
+    }
+
+    #[test]
+    fn build_and_parse_connstring_round_trip_a_value_with_delimiters() {
+        unsafe {
+            let driver = CString::new("pn532_uart").unwrap();
+            let param_name = CString::new("key").unwrap();
+            // Contains both delimiter bytes and a non-printable byte.
+            let param_value = CString::new("a:b=c\x01d").unwrap();
+
+            let mut dest = [0u8; 128];
+            let rc = nfc_build_connstring(
+                dest.as_mut_ptr() as *mut c_char,
+                dest.len(),
+                driver.as_ptr(),
+                param_name.as_ptr(),
+                param_value.as_ptr(),
+            );
+            assert_eq!(rc, NFC_COMMON_SUCCESS);
+
+            let built = CStr::from_ptr(dest.as_ptr() as *const c_char);
+            assert!(built.to_bytes().windows(3).any(|w| w == b"%01"));
+
+            let mut recovered = [0u8; 64];
+            let rc = nfc_parse_connstring(
+                built.as_ptr(),
+                driver.as_ptr(),
+                param_name.as_ptr(),
+                recovered.as_mut_ptr() as *mut c_char,
+                recovered.len(),
+            );
+            assert_eq!(rc, NFC_COMMON_SUCCESS);
+            let recovered_value = CStr::from_ptr(recovered.as_ptr() as *const c_char);
+            assert_eq!(recovered_value.to_bytes(), b"a:b=c\x01d");
+        }
+    }
+
+    #[test]
+    fn parse_connstring_rejects_invalid_percent_escape() {
+        unsafe {
+            let driver = CString::new("pn532_uart").unwrap();
+            let param_name = CString::new("key").unwrap();
+            let conn = CString::new("pn532_uart:key=bad%zzvalue").unwrap();
+            let mut out = [0u8; 64];
+
+            let rc = nfc_parse_connstring(
+                conn.as_ptr(),
+                driver.as_ptr(),
+                param_name.as_ptr(),
+                out.as_mut_ptr() as *mut c_char,
+                out.len(),
+            );
+            assert_eq!(rc, NFC_COMMON_INVALID);
+        }
+    }
+
+    #[test]
+    fn raw_variants_do_not_decode_or_encode() {
+        unsafe {
+            let driver = CString::new("pn532_uart").unwrap();
+            let param_name = CString::new("key").unwrap();
+            let param_value = CString::new("literal%2Fvalue").unwrap();
+
+            let mut dest = [0u8; 128];
+            let rc = nfc_build_connstring_raw(
+                dest.as_mut_ptr() as *mut c_char,
+                dest.len(),
+                driver.as_ptr(),
+                param_name.as_ptr(),
+                param_value.as_ptr(),
+            );
+            assert_eq!(rc, NFC_COMMON_SUCCESS);
+            let built = CStr::from_ptr(dest.as_ptr() as *const c_char);
+            assert!(built.to_bytes().ends_with(b"literal%2Fvalue"));
+
+            let mut recovered = [0u8; 64];
+            let rc = nfc_parse_connstring_raw(
+                built.as_ptr(),
+                driver.as_ptr(),
+                param_name.as_ptr(),
+                recovered.as_mut_ptr() as *mut c_char,
+                recovered.len(),
+            );
+            assert_eq!(rc, NFC_COMMON_SUCCESS);
+            let recovered_value = CStr::from_ptr(recovered.as_ptr() as *const c_char);
+            assert_eq!(recovered_value.to_bytes(), b"literal%2Fvalue");
+        }
+    }
+
+    #[test]
+    fn build_connstring_wipes_intermediate_buffers_on_success() {
+        unsafe {
+            test_reset_wipe_count();
+
+            let driver = CString::new("pn532_uart").unwrap();
+            let param_name = CString::new("key").unwrap();
+            let param_value = CString::new("super:secret").unwrap();
+            let mut dest = [0u8; 128];
+
+            let rc = nfc_build_connstring(
+                dest.as_mut_ptr() as *mut c_char,
+                dest.len(),
+                driver.as_ptr(),
+                param_name.as_ptr(),
+                param_value.as_ptr(),
+            );
+            assert_eq!(rc, NFC_COMMON_SUCCESS);
+            // One wipe for the percent-encoded value, one for the
+            // assembled connstring itself.
+            assert_eq!(test_wipe_count(), 2);
+        }
+    }
+
+    #[test]
+    fn build_connstring_wipes_intermediate_buffers_on_overflow() {
+        unsafe {
+            test_reset_wipe_count();
+
+            let driver = CString::new("pn532_uart").unwrap();
+            let param_name = CString::new("key").unwrap();
+            let param_value = CString::new("super:secret").unwrap();
+            let mut dest = [0u8; 1];
+
+            let rc = nfc_build_connstring(
+                dest.as_mut_ptr() as *mut c_char,
+                dest.len(),
+                driver.as_ptr(),
+                param_name.as_ptr(),
+                param_value.as_ptr(),
+            );
+            assert_eq!(rc, NFC_COMMON_ERROR);
+            assert_eq!(test_wipe_count(), 2);
+        }
+    }
+
+    #[test]
+    fn parse_connstring_wipes_decoded_value_on_success() {
+        unsafe {
+            test_reset_wipe_count();
+
+            let driver = CString::new("pn532_uart").unwrap();
+            let param_name = CString::new("key").unwrap();
+            let conn = CString::new("pn532_uart:key=super%3Asecret").unwrap();
+            let mut out = [0u8; 64];
+
+            let rc = nfc_parse_connstring(
+                conn.as_ptr(),
+                driver.as_ptr(),
+                param_name.as_ptr(),
+                out.as_mut_ptr() as *mut c_char,
+                out.len(),
+            );
+            assert_eq!(rc, NFC_COMMON_SUCCESS);
+            // One wipe for the scratch `pattern` buffer, one for the
+            // percent-decoded value.
+            assert_eq!(test_wipe_count(), 2);
+        }
+    }
+
+    #[test]
+    fn parse_connstring_wipes_decoded_value_on_overflow() {
+        unsafe {
+            test_reset_wipe_count();
+
+            let driver = CString::new("pn532_uart").unwrap();
+            let param_name = CString::new("key").unwrap();
+            let conn = CString::new("pn532_uart:key=super%3Asecretvalue").unwrap();
+            let mut out = [0u8; 4];
+
+            let rc = nfc_parse_connstring(
+                conn.as_ptr(),
+                driver.as_ptr(),
+                param_name.as_ptr(),
+                out.as_mut_ptr() as *mut c_char,
+                out.len(),
+            );
+            assert_eq!(rc, NFC_COMMON_ERROR);
+            assert_eq!(test_wipe_count(), 2);
+        }
+    }
+
+    #[test]
+    fn parse_all_tokenizes_driver_and_parameters() {
+        unsafe {
+            let conn = CString::new("pn53x_usb:/dev/usb:speed=115200").unwrap();
+            let mut out: [NfcConnParam; 3] = std::mem::zeroed();
+            let mut out_len: size_t = 0;
+
+            let rc = nfc_connstring_parse_all(
+                conn.as_ptr(),
+                out.as_mut_ptr(),
+                out.len() as size_t,
+                &mut out_len,
+            );
+            assert_eq!(rc, NFC_COMMON_SUCCESS);
+            assert_eq!(out_len, 3);
+
+            assert_eq!(CStr::from_ptr(out[0].key).to_bytes(), b"");
+            assert_eq!(CStr::from_ptr(out[0].value).to_bytes(), b"pn53x_usb");
+
+            assert_eq!(CStr::from_ptr(out[1].key).to_bytes(), b"");
+            assert_eq!(CStr::from_ptr(out[1].value).to_bytes(), b"/dev/usb");
+
+            assert_eq!(CStr::from_ptr(out[2].key).to_bytes(), b"speed");
+            assert_eq!(CStr::from_ptr(out[2].value).to_bytes(), b"115200");
+
+            nfc_connstring_free_all(out.as_mut_ptr(), out_len);
+        }
+    }
+
+    #[test]
+    fn parse_all_reports_required_count_when_buffer_too_small() {
+        unsafe {
+            let conn = CString::new("pn532_uart:key1=a:key2=b").unwrap();
+            let mut out_len: size_t = 0;
+
+            let rc = nfc_connstring_parse_all(conn.as_ptr(), ptr::null_mut(), 0, &mut out_len);
+            assert_eq!(rc, NFC_COMMON_ERROR);
+            assert_eq!(out_len, 3);
+
+            let mut out: [NfcConnParam; 3] = std::mem::zeroed();
+            let rc =
+                nfc_connstring_parse_all(conn.as_ptr(), out.as_mut_ptr(), 2, &mut out_len);
+            assert_eq!(rc, NFC_COMMON_ERROR);
+            assert_eq!(out_len, 3);
+        }
+    }
+
+    #[test]
+    fn parse_all_rejects_null_connstring() {
+        unsafe {
+            let mut out_len: size_t = 0;
+            let rc = nfc_connstring_parse_all(ptr::null(), ptr::null_mut(), 0, &mut out_len);
+            assert_eq!(rc, NFC_COMMON_INVALID);
+        }
+    }
+
+    #[test]
+    fn parse_all_handles_parameter_with_no_equals_sign() {
+        unsafe {
+            let conn = CString::new("pn532_uart:bareword").unwrap();
+            let mut out: [NfcConnParam; 2] = std::mem::zeroed();
+            let mut out_len: size_t = 0;
+
+            let rc = nfc_connstring_parse_all(
+                conn.as_ptr(),
+                out.as_mut_ptr(),
+                out.len() as size_t,
+                &mut out_len,
+            );
+            assert_eq!(rc, NFC_COMMON_SUCCESS);
+            assert_eq!(out_len, 2);
+            assert_eq!(CStr::from_ptr(out[1].key).to_bytes(), b"");
+            assert_eq!(CStr::from_ptr(out[1].value).to_bytes(), b"bareword");
+
+            nfc_connstring_free_all(out.as_mut_ptr(), out_len);
+        }
+    }
 }
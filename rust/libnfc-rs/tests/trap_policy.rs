@@ -0,0 +1,73 @@
+// tests/trap_policy.rs
+//
+// Integration test for the trap-on-violation policy. Enabling the
+// policy causes an invariant violation to abort the process via a trap
+// instruction instead of returning an error code, so this
+// has to be observed from a subprocess rather than in-process.
+
+#[cfg(unix)]
+mod tests {
+    use libc::c_char;
+    use std::env;
+    use std::os::unix::process::ExitStatusExt;
+    use std::process::Command;
+
+    // Provide a no-op C symbol for the library's log hook so the
+    // integration test binary links cleanly. The production build
+    // links to a real logging implementation; integration tests only
+    // need a stub.
+    #[no_mangle]
+    pub extern "C" fn log_put_message(
+        _group: u8,
+        _category: *const c_char,
+        _priority: u8,
+        _message: *const c_char,
+    ) {
+        // intentionally no-op
+    }
+
+    // Helper test executed inside a subprocess. The parent harness will
+    // spawn the current test executable with the environment variable
+    // `LIBNFC_TRAP_CHILD=1` set, which enables the trap policy and
+    // triggers a NULL-pointer violation.
+    #[test]
+    fn trap_policy_child_helper() {
+        if env::var("LIBNFC_TRAP_CHILD").ok().as_deref() != Some("1") {
+            return;
+        }
+
+        assert_eq!(libnfc_rs::nfc_secure_set_trap_on_violation(1), 0);
+        unsafe {
+            libnfc_rs::nfc_secure_memset(std::ptr::null_mut(), 0, 16);
+        }
+        // Should never reach here: the call above must trap.
+        std::process::exit(1);
+    }
+
+    #[test]
+    fn trap_on_violation_aborts_the_process() {
+        let exe = std::env::current_exe().expect("current_exe");
+        let mut cmd = Command::new(exe);
+        cmd.arg("trap_policy_child_helper");
+        cmd.env("LIBNFC_TRAP_CHILD", "1");
+        let out = cmd.output().expect("failed to spawn child");
+        let status = out.status;
+        if let Some(sig) = status.signal() {
+            if sig == libc::SIGILL || sig == libc::SIGTRAP || sig == libc::SIGABRT {
+                return;
+            }
+            panic!(
+                "child died with unexpected signal {}. stdout='{}' stderr='{}'",
+                sig,
+                String::from_utf8_lossy(&out.stdout),
+                String::from_utf8_lossy(&out.stderr)
+            );
+        }
+        panic!(
+            "child did not trap as expected; exited with code {:?}. stdout='{}' stderr='{}'",
+            status.code(),
+            String::from_utf8_lossy(&out.stdout),
+            String::from_utf8_lossy(&out.stderr)
+        );
+    }
+}